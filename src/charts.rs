@@ -0,0 +1,185 @@
+use serde::Serialize;
+use std::collections::HashMap;
+
+use crate::models::Song;
+use crate::storage::{self, Storage};
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// The ranking window for a chart: lifetime totals, or plays within a
+/// rolling window derived from the scrobble listen log.
+pub enum Period {
+    Week,
+    Month,
+    All,
+}
+
+impl Period {
+    pub fn parse(value: Option<&str>) -> Period {
+        match value {
+            Some("week") => Period::Week,
+            Some("month") => Period::Month,
+            _ => Period::All,
+        }
+    }
+
+    fn since(&self) -> Option<i64> {
+        match self {
+            Period::Week => Some(storage::time::now() - 7 * SECONDS_PER_DAY),
+            Period::Month => Some(storage::time::now() - 30 * SECONDS_PER_DAY),
+            Period::All => None,
+        }
+    }
+}
+
+/// The dimension a chart ranks over.
+pub enum Entity {
+    Songs,
+    Artists,
+    Genres,
+}
+
+impl Entity {
+    pub fn parse(value: Option<&str>) -> Entity {
+        match value {
+            Some("artists") => Entity::Artists,
+            Some("genres") => Entity::Genres,
+            _ => Entity::Songs,
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct RankedSong {
+    pub rank: usize,
+    #[serde(flatten)]
+    pub song: Song,
+    pub plays_in_period: usize,
+}
+
+#[derive(Serialize)]
+pub struct RankedArtist {
+    pub rank: usize,
+    pub artist: String,
+    pub plays_in_period: usize,
+}
+
+#[derive(Serialize)]
+pub struct RankedGenre {
+    pub rank: usize,
+    pub genre: String,
+    pub plays_in_period: usize,
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum Chart {
+    Songs(Vec<RankedSong>),
+    Artists(Vec<RankedArtist>),
+    Genres(Vec<RankedGenre>),
+}
+
+/// Builds a top-`limit` chart for `entity`, optionally restricted to
+/// `genre` and ranked by plays within `period` rather than lifetime totals.
+pub async fn build_chart(
+    storage: &dyn Storage,
+    entity: Entity,
+    limit: usize,
+    genre: Option<&str>,
+    period: Period,
+) -> Chart {
+    let since = period.since();
+
+    let mut songs = storage.list_songs().await;
+    if let Some(genre) = genre {
+        songs.retain(|song| song.genre.eq_ignore_ascii_case(genre));
+    }
+
+    let plays_by_song: HashMap<usize, usize> = if since.is_some() {
+        let mut plays = HashMap::new();
+        for event in storage.listen_events(since).await {
+            *plays.entry(event.song_id).or_insert(0) += 1;
+        }
+        plays
+    } else {
+        songs.iter().map(|song| (song.id, song.play_count)).collect()
+    };
+
+    match entity {
+        Entity::Songs => {
+            let mut ranked: Vec<_> = songs
+                .into_iter()
+                .map(|song| {
+                    let plays = *plays_by_song.get(&song.id).unwrap_or(&0);
+                    (song, plays)
+                })
+                .collect();
+            ranked.sort_by_key(|(_, plays)| std::cmp::Reverse(*plays));
+            Chart::Songs(
+                ranked
+                    .into_iter()
+                    .take(limit)
+                    .enumerate()
+                    .map(|(i, (song, plays))| RankedSong {
+                        rank: i + 1,
+                        song,
+                        plays_in_period: plays,
+                    })
+                    .collect(),
+            )
+        }
+        Entity::Artists => {
+            let artist_names: HashMap<usize, String> = storage
+                .list_artists()
+                .await
+                .into_iter()
+                .map(|artist| (artist.id, artist.name))
+                .collect();
+
+            let mut plays_by_artist: HashMap<String, usize> = HashMap::new();
+            for song in &songs {
+                let plays = *plays_by_song.get(&song.id).unwrap_or(&0);
+                let name = artist_names
+                    .get(&song.artist_id)
+                    .cloned()
+                    .unwrap_or_else(|| "Unknown Artist".to_string());
+                *plays_by_artist.entry(name).or_insert(0) += plays;
+            }
+            let mut ranked: Vec<_> = plays_by_artist.into_iter().collect();
+            ranked.sort_by_key(|(_, plays)| std::cmp::Reverse(*plays));
+            Chart::Artists(
+                ranked
+                    .into_iter()
+                    .take(limit)
+                    .enumerate()
+                    .map(|(i, (artist, plays_in_period))| RankedArtist {
+                        rank: i + 1,
+                        artist,
+                        plays_in_period,
+                    })
+                    .collect(),
+            )
+        }
+        Entity::Genres => {
+            let mut plays_by_genre: HashMap<String, usize> = HashMap::new();
+            for song in &songs {
+                let plays = *plays_by_song.get(&song.id).unwrap_or(&0);
+                *plays_by_genre.entry(song.genre.clone()).or_insert(0) += plays;
+            }
+            let mut ranked: Vec<_> = plays_by_genre.into_iter().collect();
+            ranked.sort_by_key(|(_, plays)| std::cmp::Reverse(*plays));
+            Chart::Genres(
+                ranked
+                    .into_iter()
+                    .take(limit)
+                    .enumerate()
+                    .map(|(i, (genre, plays_in_period))| RankedGenre {
+                        rank: i + 1,
+                        genre,
+                        plays_in_period,
+                    })
+                    .collect(),
+            )
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use lofty::{Accessor, AudioFile, Probe, TaggedFileExt};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+use crate::models::ScannedSong;
+use crate::storage::Storage;
+
+const AUDIO_EXTENSIONS: &[&str] = &["mp3", "flac", "wav", "ogg", "m4a", "aac"];
+
+/// Result of a `POST /library/scan`.
+#[derive(Serialize)]
+pub struct ScanSummary {
+    pub added: usize,
+    pub skipped: usize,
+    pub errors: usize,
+}
+
+fn is_audio_file(path: &std::path::Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| AUDIO_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+        .unwrap_or(false)
+}
+
+/// Reads tags from an audio file with `lofty`, falling back to the file
+/// stem for the title and placeholder values for artist/genre when a tag
+/// is missing, so a scan never drops a file just because it's under-tagged.
+fn read_scanned_song(path: &std::path::Path) -> lofty::Result<ScannedSong> {
+    let tagged_file = Probe::open(path)?.read()?;
+    let properties = tagged_file.properties();
+    let tag = tagged_file.primary_tag().or_else(|| tagged_file.first_tag());
+
+    let title = tag
+        .and_then(|t| t.title())
+        .map(|s| s.to_string())
+        .unwrap_or_else(|| {
+            path.file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or("Unknown Title")
+                .to_string()
+        });
+    let artist = tag
+        .and_then(|t| t.artist())
+        .map(|s| s.to_string())
+        .unwrap_or_else(|| "Unknown Artist".to_string());
+    let album = tag.and_then(|t| t.album()).map(|s| s.to_string());
+    let genre = tag
+        .and_then(|t| t.genre())
+        .map(|s| s.to_string())
+        .unwrap_or_else(|| "Unknown".to_string());
+    let track_number = tag.and_then(|t| t.track());
+    let year = tag.and_then(|t| t.year()).map(|y| y as i32);
+
+    Ok(ScannedSong {
+        title,
+        artist,
+        album,
+        year,
+        genre,
+        track_number,
+        duration: Some(properties.duration().as_secs_f64()),
+        file_path: path.to_string_lossy().to_string(),
+    })
+}
+
+/// Resolves `requested` as a path relative to `library_root`, rejecting
+/// anything that canonicalizes outside of it. Confines `POST
+/// /library/scan` (and, by extension, `GET /songs/{id}/stream`, since it
+/// only ever serves paths a scan recorded) to a server-configured root
+/// instead of letting a client walk or read arbitrary host files.
+fn resolve_within_root(library_root: &Path, requested: &str) -> Result<PathBuf, String> {
+    let candidate = library_root.join(requested);
+    let canonical = candidate
+        .canonicalize()
+        .map_err(|e| format!("{}: {}", candidate.display(), e))?;
+    if !canonical.starts_with(library_root) {
+        return Err(format!(
+            "{} is outside the library root",
+            candidate.display()
+        ));
+    }
+    Ok(canonical)
+}
+
+/// Recursively walks `requested_path` (resolved under `library_root`),
+/// reading tags from every audio file found and inserting a `Song` for
+/// each one not already known by `file_path`.
+pub async fn scan_directory(
+    storage: &dyn Storage,
+    library_root: &Path,
+    requested_path: &str,
+) -> Result<ScanSummary, String> {
+    let root = resolve_within_root(library_root, requested_path)?;
+    let mut summary = ScanSummary {
+        added: 0,
+        skipped: 0,
+        errors: 0,
+    };
+
+    for entry in WalkDir::new(root).into_iter().filter_map(|e| e.ok()) {
+        let path = entry.path();
+        if !entry.file_type().is_file() || !is_audio_file(path) {
+            continue;
+        }
+
+        let path_str = path.to_string_lossy().to_string();
+        if storage.find_by_path(&path_str).await.is_some() {
+            summary.skipped += 1;
+            continue;
+        }
+
+        match read_scanned_song(path) {
+            Ok(song) => match storage.insert_scanned_song(song).await {
+                Ok(_) => summary.added += 1,
+                Err(e) => {
+                    eprintln!("Error inserting {}: {}", path.display(), e);
+                    summary.errors += 1;
+                }
+            },
+            Err(e) => {
+                eprintln!("Error reading tags from {}: {}", path.display(), e);
+                summary.errors += 1;
+            }
+        }
+    }
+
+    Ok(summary)
+}
@@ -1,160 +1,380 @@
-use serde::{Deserialize, Serialize};
-use warp::Filter;
 use dashmap::DashMap;
-use std::{sync::Arc, fs, path::Path};
-
-#[derive(Serialize, Deserialize, Clone)]
-struct Song {
-    id: usize,
-    title: String,
-    artist: String,
-    genre: String,
-    play_count: usize,
-}
+use std::sync::Arc;
+use warp::Filter;
 
-#[derive(Deserialize)]
-struct NewSong {
-    title: String,
-    artist: String,
-    genre: String,
-}
+mod charts;
+mod library;
+mod models;
+mod response;
+mod storage;
+mod streaming;
+
+use models::{NewSong, RateRequest, ScanRequest};
+use response::ApiResponse;
+use storage::{JsonFileStorage, MemoryStorage, SqliteStorage, Storage};
 
 #[derive(Default)]
 struct AppState {
     visit_count: DashMap<String, usize>,
-    music_library: DashMap<usize, Song>,
-    next_song_id: DashMap<String, usize>,
 }
 
 const DATA_FILE: &str = "songs.json";
+const DEFAULT_LIBRARY_ROOT: &str = ".";
 
-fn load_data() -> DashMap<usize, Song> {
-    let map = DashMap::new();
+/// Resolves the server-configured library root (`LIBRARY_ROOT` env var,
+/// defaulting to the working directory) to an absolute path. `/library/scan`
+/// and `/songs/{id}/stream` are confined to this directory so a client
+/// can't walk or read arbitrary files on the host.
+fn resolve_library_root() -> std::path::PathBuf {
+    let root = std::env::var("LIBRARY_ROOT").unwrap_or_else(|_| DEFAULT_LIBRARY_ROOT.to_string());
+    std::fs::canonicalize(&root)
+        .unwrap_or_else(|e| panic!("Error resolving LIBRARY_ROOT {}: {}", root, e))
+}
 
-    if Path::new(DATA_FILE).exists() {
-        match fs::read_to_string(DATA_FILE) {
-            Ok(data) => match serde_json::from_str::<Vec<Song>>(&data) {
-                Ok(songs) => {
-                    for song in songs {
-                        map.insert(song.id, song);
-                    }
-                }
+/// Builds the storage backend selected by the `STORAGE_BACKEND` env var
+/// (`memory`, `json` [default], or `sqlite`; `DATABASE_URL` configures the
+/// sqlite connection, defaulting to `sqlite://songs.db`).
+async fn build_storage() -> Arc<dyn Storage> {
+    match std::env::var("STORAGE_BACKEND").as_deref() {
+        Ok("memory") => Arc::new(MemoryStorage::new()),
+        Ok("sqlite") => {
+            let database_url =
+                std::env::var("DATABASE_URL").unwrap_or_else(|_| "sqlite://songs.db".to_string());
+            match SqliteStorage::connect(&database_url).await {
+                Ok(storage) => Arc::new(storage),
                 Err(e) => {
-                    eprintln!("Error parsing songs.json: {}", e);
+                    eprintln!(
+                        "Error connecting to {}: {}, falling back to JSON file storage",
+                        database_url, e
+                    );
+                    Arc::new(JsonFileStorage::new(DATA_FILE))
                 }
-            },
-            Err(e) => {
-                eprintln!("Error reading songs.json: {}", e);
             }
         }
+        _ => Arc::new(JsonFileStorage::new(DATA_FILE)),
     }
-
-    map
-}
-
-fn save_data(library: &DashMap<usize, Song>) {
-    let songs: Vec<_> = library.iter().map(|entry| entry.clone()).collect();
-    let json = serde_json::to_string_pretty(&songs).unwrap();
-    fs::write(DATA_FILE, json).unwrap();
 }
 
 #[tokio::main]
 async fn main() {
-    let state = Arc::new(AppState {
-        visit_count: DashMap::new(),
-        music_library: load_data(),
-        next_song_id: DashMap::new(),
-    });
+    let state = Arc::new(AppState::default());
+    let storage = build_storage().await;
+    let library_root = resolve_library_root();
 
     // Basic route
-    let index = warp::path::end()
-        .map(|| warp::reply::html("Welcome to the Rust-powered web server!"));
+    let index =
+        warp::path::end().map(|| warp::reply::html("Welcome to the Rust-powered web server!"));
 
     // Visit count
     let visit_count = {
         let state = Arc::clone(&state);
-        warp::path("count")
-            .map(move || {
-                let mut count = state
-                    .visit_count
-                    .entry("count".to_string())
-                    .or_insert(0);
-                *count += 1;
-                format!("Visit count: {}", *count)
-            })
+        warp::path("count").map(move || {
+            let mut count = state.visit_count.entry("count".to_string()).or_insert(0);
+            *count += 1;
+            format!("Visit count: {}", *count)
+        })
     };
 
     // Add song
     let add_song = {
-        let state = Arc::clone(&state);
+        let storage = Arc::clone(&storage);
         warp::path!("songs" / "new")
             .and(warp::post())
             .and(warp::body::json())
-            .map(move |new_song: NewSong| {
-                // Generate a new unique ID for the song
-                let mut id = state.next_song_id.entry("next_id".to_string()).or_insert(1);
-                let song = Song {
-                    id: *id,
-                    title: new_song.title,
-                    artist: new_song.artist,
-                    genre: new_song.genre,
-                    play_count: 0,
-                };
-                *id += 1; // Increment for the next song
-    
-                // Insert the new song into the library
-                state.music_library.insert(song.id, song.clone());
-                warp::reply::json(&song) // Respond with the created song
+            .and_then(move |new_song: NewSong| {
+                let storage = Arc::clone(&storage);
+                async move {
+                    let reply = match storage.insert_song(new_song).await {
+                        Ok(song) => ApiResponse::Success(song).into_reply(),
+                        Err(e) => ApiResponse::<models::Song>::Fatal(e.to_string()).into_reply(),
+                    };
+                    Ok::<_, std::convert::Infallible>(reply)
+                }
             })
     };
 
     // Search songs
     let search_songs = {
-        let state = Arc::clone(&state);
+        let storage = Arc::clone(&storage);
         warp::path!("songs" / "search")
             .and(warp::query::<std::collections::HashMap<String, String>>())
-            .map(move |query: std::collections::HashMap<String, String>| {
-                let results: Vec<_> = state
-                    .music_library
-                    .iter()
-                    .filter(|entry| {
-                        let song = entry.value();
-                        query.iter().all(|(key, value)| {
-                            match key.as_str() {
-                                "title" => song.title.contains(value),
-                                "artist" => song.artist.contains(value),
-                                "genre" => song.genre.contains(value),
-                                _ => false,
-                            }
-                        })
-                    })
-                    .map(|entry| entry.clone())
-                    .collect();
-                warp::reply::json(&results)
+            .and_then(move |query: std::collections::HashMap<String, String>| {
+                let storage = Arc::clone(&storage);
+                async move {
+                    let results = storage.search(&query).await;
+                    Ok::<_, std::convert::Infallible>(ApiResponse::Success(results).into_reply())
+                }
             })
     };
 
     // Play song
     let play_song = {
-        let state = Arc::clone(&state);
-        warp::path!("songs" / "play" / usize)
-            .map(move |id: usize| {
-                if let Some(mut song) = state.music_library.get_mut(&id) {
-                    song.play_count += 1;
-                    warp::reply::json(&*song)
-                } else {
-                    warp::reply::json(&serde_json::json!({ "error": "Song not found" }))
+        let storage = Arc::clone(&storage);
+        warp::path!("songs" / "play" / usize).and_then(move |id: usize| {
+            let storage = Arc::clone(&storage);
+            async move {
+                let reply = match storage.increment_play_count(id).await {
+                    Ok(Some(song)) => ApiResponse::Success(song).into_reply(),
+                    Ok(None) => {
+                        ApiResponse::<models::Song>::Failure(format!("Song {} not found", id))
+                            .into_reply()
+                    }
+                    Err(e) => ApiResponse::<models::Song>::Fatal(e.to_string()).into_reply(),
+                };
+                Ok::<_, std::convert::Infallible>(reply)
+            }
+        })
+    };
+
+    // Star / unstar a song
+    let star_song = {
+        let storage = Arc::clone(&storage);
+        warp::path!("songs" / usize / "star")
+            .and(warp::post())
+            .and_then(move |id: usize| {
+                let storage = Arc::clone(&storage);
+                async move {
+                    let reply = match storage.set_starred(id, true).await {
+                        Ok(Some(song)) => ApiResponse::Success(song).into_reply(),
+                        Ok(None) => {
+                            ApiResponse::<models::Song>::Failure(format!("Song {} not found", id))
+                                .into_reply()
+                        }
+                        Err(e) => ApiResponse::<models::Song>::Fatal(e.to_string()).into_reply(),
+                    };
+                    Ok::<_, std::convert::Infallible>(reply)
+                }
+            })
+    };
+
+    let unstar_song = {
+        let storage = Arc::clone(&storage);
+        warp::path!("songs" / usize / "unstar")
+            .and(warp::post())
+            .and_then(move |id: usize| {
+                let storage = Arc::clone(&storage);
+                async move {
+                    let reply = match storage.set_starred(id, false).await {
+                        Ok(Some(song)) => ApiResponse::Success(song).into_reply(),
+                        Ok(None) => {
+                            ApiResponse::<models::Song>::Failure(format!("Song {} not found", id))
+                                .into_reply()
+                        }
+                        Err(e) => ApiResponse::<models::Song>::Fatal(e.to_string()).into_reply(),
+                    };
+                    Ok::<_, std::convert::Infallible>(reply)
+                }
+            })
+    };
+
+    // Rate a song 1..=5
+    let rate_song = {
+        let storage = Arc::clone(&storage);
+        warp::path!("songs" / usize / "rate")
+            .and(warp::post())
+            .and(warp::body::json())
+            .and_then(move |id: usize, body: RateRequest| {
+                let storage = Arc::clone(&storage);
+                async move {
+                    if !(1..=5).contains(&body.rating) {
+                        return Ok::<_, std::convert::Infallible>(
+                            ApiResponse::<models::Song>::Failure(format!(
+                                "Rating must be between 1 and 5, got {}",
+                                body.rating
+                            ))
+                            .into_reply(),
+                        );
+                    }
+                    let reply = match storage.set_rating(id, body.rating).await {
+                        Ok(Some(song)) => ApiResponse::Success(song).into_reply(),
+                        Ok(None) => {
+                            ApiResponse::<models::Song>::Failure(format!("Song {} not found", id))
+                                .into_reply()
+                        }
+                        Err(e) => ApiResponse::<models::Song>::Fatal(e.to_string()).into_reply(),
+                    };
+                    Ok::<_, std::convert::Infallible>(reply)
+                }
+            })
+    };
+
+    // Scrobble: records a listen, bumping play_count
+    let scrobble_song = {
+        let storage = Arc::clone(&storage);
+        warp::path!("songs" / usize / "scrobble")
+            .and(warp::post())
+            .and_then(move |id: usize| {
+                let storage = Arc::clone(&storage);
+                async move {
+                    let reply = match storage.scrobble(id).await {
+                        Ok(Some(song)) => ApiResponse::Success(song).into_reply(),
+                        Ok(None) => {
+                            ApiResponse::<models::Song>::Failure(format!("Song {} not found", id))
+                                .into_reply()
+                        }
+                        Err(e) => ApiResponse::<models::Song>::Fatal(e.to_string()).into_reply(),
+                    };
+                    Ok::<_, std::convert::Infallible>(reply)
+                }
+            })
+    };
+
+    // Starred songs
+    let starred_songs = {
+        let storage = Arc::clone(&storage);
+        warp::path!("songs" / "starred").and_then(move || {
+            let storage = Arc::clone(&storage);
+            async move {
+                let songs = storage.list_starred().await;
+                Ok::<_, std::convert::Infallible>(ApiResponse::Success(songs).into_reply())
+            }
+        })
+    };
+
+    // Stream a song's audio file, honoring Range requests. Re-checks the
+    // stored path against the library root (rather than trusting it was
+    // always written by a confined scan) before reading it back.
+    let stream_song = {
+        let storage = Arc::clone(&storage);
+        let library_root = library_root.clone();
+        warp::path!("songs" / usize / "stream")
+            .and(warp::header::optional::<String>("range"))
+            .and_then(move |id: usize, range: Option<String>| {
+                let storage = Arc::clone(&storage);
+                let library_root = library_root.clone();
+                async move {
+                    let path = match storage.get_song(id).await.and_then(|song| song.file_path) {
+                        Some(path) => path,
+                        None => return Err(warp::reject::not_found()),
+                    };
+                    match std::fs::canonicalize(&path) {
+                        Ok(canonical) if canonical.starts_with(&library_root) => {
+                            streaming::stream_file(&path, range).await
+                        }
+                        _ => Err(warp::reject::not_found()),
+                    }
+                }
+            })
+    };
+
+    // Charts: top songs/artists/genres by play count
+    let charts = {
+        let storage = Arc::clone(&storage);
+        warp::path!("songs" / "charts")
+            .and(warp::query::<std::collections::HashMap<String, String>>())
+            .and_then(move |query: std::collections::HashMap<String, String>| {
+                let storage = Arc::clone(&storage);
+                async move {
+                    let limit = query
+                        .get("limit")
+                        .and_then(|v| v.parse::<usize>().ok())
+                        .unwrap_or(20);
+                    let genre = query.get("genre").map(|s| s.as_str());
+                    let entity = charts::Entity::parse(query.get("entity").map(|s| s.as_str()));
+                    let period = charts::Period::parse(query.get("period").map(|s| s.as_str()));
+                    let chart =
+                        charts::build_chart(storage.as_ref(), entity, limit, genre, period).await;
+                    Ok::<_, std::convert::Infallible>(ApiResponse::Success(chart).into_reply())
+                }
+            })
+    };
+
+    // List all artists
+    let list_artists = {
+        let storage = Arc::clone(&storage);
+        warp::path!("artists").and_then(move || {
+            let storage = Arc::clone(&storage);
+            async move {
+                let artists = storage.list_artists().await;
+                Ok::<_, std::convert::Infallible>(ApiResponse::Success(artists).into_reply())
+            }
+        })
+    };
+
+    // List an artist's albums
+    let artist_albums = {
+        let storage = Arc::clone(&storage);
+        warp::path!("artists" / usize / "albums").and_then(move |artist_id: usize| {
+            let storage = Arc::clone(&storage);
+            async move {
+                let reply = match storage.get_artist(artist_id).await {
+                    Some(_) => {
+                        ApiResponse::Success(storage.list_albums_by_artist(artist_id).await)
+                            .into_reply()
+                    }
+                    None => {
+                        ApiResponse::<Vec<models::Album>>::Failure(format!(
+                            "Artist {} not found",
+                            artist_id
+                        ))
+                        .into_reply()
+                    }
+                };
+                Ok::<_, std::convert::Infallible>(reply)
+            }
+        })
+    };
+
+    // List an album's songs
+    let album_songs = {
+        let storage = Arc::clone(&storage);
+        warp::path!("albums" / usize / "songs").and_then(move |album_id: usize| {
+            let storage = Arc::clone(&storage);
+            async move {
+                let songs = storage.list_songs_by_album(album_id).await;
+                Ok::<_, std::convert::Infallible>(ApiResponse::Success(songs).into_reply())
+            }
+        })
+    };
+
+    // Scan a directory and ingest any audio files found into the library.
+    // `request.path` is resolved relative to the library root and rejected
+    // if it escapes it.
+    let scan_library = {
+        let storage = Arc::clone(&storage);
+        let library_root = library_root.clone();
+        warp::path!("library" / "scan")
+            .and(warp::post())
+            .and(warp::body::json())
+            .and_then(move |request: ScanRequest| {
+                let storage = Arc::clone(&storage);
+                let library_root = library_root.clone();
+                async move {
+                    let reply =
+                        match library::scan_directory(storage.as_ref(), &library_root, &request.path)
+                            .await
+                        {
+                            Ok(summary) => ApiResponse::Success(summary).into_reply(),
+                            Err(e) => {
+                                ApiResponse::<library::ScanSummary>::Failure(e).into_reply()
+                            }
+                        };
+                    Ok::<_, std::convert::Infallible>(reply)
                 }
             })
     };
 
     // Combine routes
-    let routes = warp::get().and(index.or(visit_count).or(search_songs).or(play_song))
-        .or(add_song);
+    let routes = warp::get()
+        .and(
+            index
+                .or(visit_count)
+                .or(search_songs)
+                .or(play_song)
+                .or(starred_songs)
+                .or(stream_song)
+                .or(charts)
+                .or(list_artists)
+                .or(artist_albums)
+                .or(album_songs),
+        )
+        .or(add_song)
+        .or(star_song)
+        .or(unstar_song)
+        .or(rate_song)
+        .or(scrobble_song)
+        .or(scan_library);
 
     println!("The server is currently listening on localhost:8080.");
     warp::serve(routes).run(([127, 0, 0, 1], 8080)).await;
-
-    // Save data before exiting
-    save_data(&state.music_library);
-}
\ No newline at end of file
+}
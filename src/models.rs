@@ -0,0 +1,71 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Song {
+    pub id: usize,
+    pub title: String,
+    pub artist_id: usize,
+    pub album_id: Option<usize>,
+    pub genre: String,
+    pub play_count: usize,
+    pub starred: bool,
+    pub rating: Option<u8>,
+    pub file_path: Option<String>,
+    pub track_number: Option<u32>,
+    pub duration: Option<f64>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Artist {
+    pub id: usize,
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Album {
+    pub id: usize,
+    pub title: String,
+    pub artist_id: usize,
+    pub year: Option<i32>,
+}
+
+#[derive(Deserialize)]
+pub struct NewSong {
+    pub title: String,
+    pub artist: String,
+    pub album: Option<String>,
+    pub genre: String,
+}
+
+#[derive(Deserialize)]
+pub struct RateRequest {
+    pub rating: u8,
+}
+
+/// A single recorded listen (a play or a scrobble). Only the song it
+/// belongs to matters for ranking; the backend does any timestamp
+/// filtering before handing events back.
+#[derive(Clone)]
+pub struct ListenEvent {
+    pub song_id: usize,
+}
+
+/// A song discovered by a library scan, with tags read from the file
+/// itself rather than supplied by a client. `artist`/`album` are the raw
+/// tag strings; the storage backend resolves or creates the matching
+/// `Artist`/`Album` rows.
+pub struct ScannedSong {
+    pub title: String,
+    pub artist: String,
+    pub album: Option<String>,
+    pub year: Option<i32>,
+    pub genre: String,
+    pub track_number: Option<u32>,
+    pub duration: Option<f64>,
+    pub file_path: String,
+}
+
+#[derive(Deserialize)]
+pub struct ScanRequest {
+    pub path: String,
+}
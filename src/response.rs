@@ -0,0 +1,30 @@
+use serde::Serialize;
+use warp::http::StatusCode;
+use warp::reply::{json, with_status, Json, WithStatus};
+
+/// Uniform response envelope returned by every JSON route (the audio
+/// stream, index page and visit counter reply with raw bodies instead).
+///
+/// `Success` carries the normal payload, `Failure` is for recoverable /
+/// validation errors (bad input, missing resource), and `Fatal` is for
+/// internal errors the caller can't do anything about (e.g. disk I/O).
+#[derive(Serialize)]
+#[serde(tag = "type", content = "content")]
+pub enum ApiResponse<T> {
+    Success(T),
+    Failure(String),
+    Fatal(String),
+}
+
+impl<T: Serialize> ApiResponse<T> {
+    /// Builds the `warp::reply` for this response, setting the HTTP status
+    /// code to match the variant (200 / 400 / 500).
+    pub fn into_reply(self) -> WithStatus<Json> {
+        let status = match &self {
+            ApiResponse::Success(_) => StatusCode::OK,
+            ApiResponse::Failure(_) => StatusCode::BAD_REQUEST,
+            ApiResponse::Fatal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        with_status(json(&self), status)
+    }
+}
@@ -0,0 +1,327 @@
+use async_trait::async_trait;
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tokio::sync::Mutex;
+
+use super::{time, Storage, StorageError};
+use crate::models::{Album, Artist, ListenEvent, NewSong, ScannedSong, Song};
+
+/// On-disk shape of the JSON file backend: one file holds songs alongside
+/// the artists/albums they reference.
+#[derive(Default, Serialize, Deserialize)]
+struct PersistedData {
+    songs: Vec<Song>,
+    artists: Vec<Artist>,
+    albums: Vec<Album>,
+}
+
+/// JSON-file backed storage. Behaves like the server's original hard-wired
+/// `songs.json` persistence, except every mutation writes the full file
+/// immediately instead of only at shutdown, so a crash can't lose data.
+///
+/// Listen timestamps are kept in memory only; the `play_count` they
+/// accompany is persisted with each song, so windowed chart periods
+/// start empty again after a restart.
+pub struct JsonFileStorage {
+    path: PathBuf,
+    songs: DashMap<usize, Song>,
+    next_id: AtomicUsize,
+    write_lock: Mutex<()>,
+    listens: DashMap<usize, Vec<i64>>,
+    artists: DashMap<usize, Artist>,
+    next_artist_id: AtomicUsize,
+    albums: DashMap<usize, Album>,
+    next_album_id: AtomicUsize,
+}
+
+impl JsonFileStorage {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let songs = DashMap::new();
+        let artists = DashMap::new();
+        let albums = DashMap::new();
+        let mut max_song_id = 0;
+        let mut max_artist_id = 0;
+        let mut max_album_id = 0;
+
+        if path.exists() {
+            // A file we can't read or whose shape we don't recognize (e.g.
+            // the bare `Vec<Song>` format used before this backend grew
+            // artists/albums) must not be treated as "no data yet" — that
+            // would silently wipe an existing library. Refuse to start
+            // instead.
+            let data = std::fs::read_to_string(&path).unwrap_or_else(|e| {
+                panic!("Error reading {}: {}", path.display(), e);
+            });
+            let loaded = serde_json::from_str::<PersistedData>(&data).unwrap_or_else(|e| {
+                panic!(
+                    "Error parsing {}: {}. The on-disk format changed to {{songs, artists, albums}}; \
+                     back up and remove the file (or migrate it by hand) before restarting.",
+                    path.display(),
+                    e
+                );
+            });
+
+            for song in loaded.songs {
+                max_song_id = max_song_id.max(song.id);
+                songs.insert(song.id, song);
+            }
+            for artist in loaded.artists {
+                max_artist_id = max_artist_id.max(artist.id);
+                artists.insert(artist.id, artist);
+            }
+            for album in loaded.albums {
+                max_album_id = max_album_id.max(album.id);
+                albums.insert(album.id, album);
+            }
+        }
+
+        Self {
+            path,
+            songs,
+            next_id: AtomicUsize::new(max_song_id + 1),
+            write_lock: Mutex::new(()),
+            listens: DashMap::new(),
+            artists,
+            next_artist_id: AtomicUsize::new(max_artist_id + 1),
+            albums,
+            next_album_id: AtomicUsize::new(max_album_id + 1),
+        }
+    }
+
+    async fn persist(&self) -> Result<(), StorageError> {
+        let _guard = self.write_lock.lock().await;
+        let data = PersistedData {
+            songs: self.songs.iter().map(|entry| entry.value().clone()).collect(),
+            artists: self.artists.iter().map(|entry| entry.value().clone()).collect(),
+            albums: self.albums.iter().map(|entry| entry.value().clone()).collect(),
+        };
+        let json = serde_json::to_string_pretty(&data)
+            .map_err(|e| StorageError::new(format!("Error serializing library: {}", e)))?;
+        std::fs::write(&self.path, json).map_err(|e| {
+            StorageError::new(format!("Error writing {}: {}", self.path.display(), e))
+        })
+    }
+
+    fn artist_name(&self, artist_id: usize) -> Option<String> {
+        self.artists.get(&artist_id).map(|a| a.name.clone())
+    }
+}
+
+#[async_trait]
+impl Storage for JsonFileStorage {
+    async fn list_songs(&self) -> Vec<Song> {
+        self.songs.iter().map(|entry| entry.value().clone()).collect()
+    }
+
+    async fn get_song(&self, id: usize) -> Option<Song> {
+        self.songs.get(&id).map(|entry| entry.clone())
+    }
+
+    async fn insert_song(&self, new_song: NewSong) -> Result<Song, StorageError> {
+        let artist = self.find_or_create_artist(&new_song.artist).await?;
+        let album_id = match &new_song.album {
+            Some(title) => Some(self.find_or_create_album(title, artist.id, None).await?.id),
+            None => None,
+        };
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let song = Song {
+            id,
+            title: new_song.title,
+            artist_id: artist.id,
+            album_id,
+            genre: new_song.genre,
+            play_count: 0,
+            starred: false,
+            rating: None,
+            file_path: None,
+            track_number: None,
+            duration: None,
+        };
+        self.songs.insert(id, song.clone());
+        self.persist().await?;
+        Ok(song)
+    }
+
+    async fn increment_play_count(&self, id: usize) -> Result<Option<Song>, StorageError> {
+        let updated = self.songs.get_mut(&id).map(|mut entry| {
+            entry.play_count += 1;
+            entry.clone()
+        });
+        if updated.is_some() {
+            self.listens.entry(id).or_default().push(time::now());
+            self.persist().await?;
+        }
+        Ok(updated)
+    }
+
+    async fn search(&self, query: &HashMap<String, String>) -> Vec<Song> {
+        self.songs
+            .iter()
+            .filter(|entry| {
+                let song = entry.value();
+                query.iter().all(|(key, value)| match key.as_str() {
+                    "title" => song.title.contains(value),
+                    "artist" => self
+                        .artist_name(song.artist_id)
+                        .is_some_and(|name| name.contains(value)),
+                    "genre" => song.genre.contains(value),
+                    _ => false,
+                })
+            })
+            .map(|entry| entry.value().clone())
+            .collect()
+    }
+
+    async fn set_starred(&self, id: usize, starred: bool) -> Result<Option<Song>, StorageError> {
+        let updated = self.songs.get_mut(&id).map(|mut entry| {
+            entry.starred = starred;
+            entry.clone()
+        });
+        if updated.is_some() {
+            self.persist().await?;
+        }
+        Ok(updated)
+    }
+
+    async fn set_rating(&self, id: usize, rating: u8) -> Result<Option<Song>, StorageError> {
+        let updated = self.songs.get_mut(&id).map(|mut entry| {
+            entry.rating = Some(rating);
+            entry.clone()
+        });
+        if updated.is_some() {
+            self.persist().await?;
+        }
+        Ok(updated)
+    }
+
+    async fn scrobble(&self, id: usize) -> Result<Option<Song>, StorageError> {
+        self.increment_play_count(id).await
+    }
+
+    async fn list_starred(&self) -> Vec<Song> {
+        self.songs
+            .iter()
+            .filter(|entry| entry.value().starred)
+            .map(|entry| entry.value().clone())
+            .collect()
+    }
+
+    async fn find_by_path(&self, path: &str) -> Option<Song> {
+        self.songs
+            .iter()
+            .find(|entry| entry.value().file_path.as_deref() == Some(path))
+            .map(|entry| entry.value().clone())
+    }
+
+    async fn insert_scanned_song(&self, scanned: ScannedSong) -> Result<Song, StorageError> {
+        let artist = self.find_or_create_artist(&scanned.artist).await?;
+        let album_id = match scanned.album {
+            Some(title) => Some(
+                self.find_or_create_album(&title, artist.id, scanned.year)
+                    .await?
+                    .id,
+            ),
+            None => None,
+        };
+
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let song = Song {
+            id,
+            title: scanned.title,
+            artist_id: artist.id,
+            album_id,
+            genre: scanned.genre,
+            play_count: 0,
+            starred: false,
+            rating: None,
+            file_path: Some(scanned.file_path),
+            track_number: scanned.track_number,
+            duration: scanned.duration,
+        };
+        self.songs.insert(id, song.clone());
+        self.persist().await?;
+        Ok(song)
+    }
+
+    async fn listen_events(&self, since: Option<i64>) -> Vec<ListenEvent> {
+        self.listens
+            .iter()
+            .flat_map(|entry| {
+                let song_id = *entry.key();
+                entry
+                    .value()
+                    .iter()
+                    .filter(|&&timestamp| since.is_none_or(|since| timestamp >= since))
+                    .map(|_| ListenEvent { song_id })
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    async fn find_or_create_artist(&self, name: &str) -> Result<Artist, StorageError> {
+        if let Some(existing) = self.artists.iter().find(|entry| entry.value().name == name) {
+            return Ok(existing.value().clone());
+        }
+        let id = self.next_artist_id.fetch_add(1, Ordering::SeqCst);
+        let artist = Artist {
+            id,
+            name: name.to_string(),
+        };
+        self.artists.insert(id, artist.clone());
+        self.persist().await?;
+        Ok(artist)
+    }
+
+    async fn get_artist(&self, id: usize) -> Option<Artist> {
+        self.artists.get(&id).map(|entry| entry.clone())
+    }
+
+    async fn list_artists(&self) -> Vec<Artist> {
+        self.artists.iter().map(|entry| entry.value().clone()).collect()
+    }
+
+    async fn find_or_create_album(
+        &self,
+        title: &str,
+        artist_id: usize,
+        year: Option<i32>,
+    ) -> Result<Album, StorageError> {
+        if let Some(existing) = self
+            .albums
+            .iter()
+            .find(|entry| entry.value().title == title && entry.value().artist_id == artist_id)
+        {
+            return Ok(existing.value().clone());
+        }
+        let id = self.next_album_id.fetch_add(1, Ordering::SeqCst);
+        let album = Album {
+            id,
+            title: title.to_string(),
+            artist_id,
+            year,
+        };
+        self.albums.insert(id, album.clone());
+        self.persist().await?;
+        Ok(album)
+    }
+
+    async fn list_albums_by_artist(&self, artist_id: usize) -> Vec<Album> {
+        self.albums
+            .iter()
+            .filter(|entry| entry.value().artist_id == artist_id)
+            .map(|entry| entry.value().clone())
+            .collect()
+    }
+
+    async fn list_songs_by_album(&self, album_id: usize) -> Vec<Song> {
+        self.songs
+            .iter()
+            .filter(|entry| entry.value().album_id == Some(album_id))
+            .map(|entry| entry.value().clone())
+            .collect()
+    }
+}
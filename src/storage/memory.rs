@@ -0,0 +1,240 @@
+use async_trait::async_trait;
+use dashmap::DashMap;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use super::{time, Storage, StorageError};
+use crate::models::{Album, Artist, ListenEvent, NewSong, ScannedSong, Song};
+
+/// Pure in-memory backend; wraps the `DashMap` the server used before the
+/// `Storage` trait existed. Data does not survive a restart.
+#[derive(Default)]
+pub struct MemoryStorage {
+    songs: DashMap<usize, Song>,
+    next_id: AtomicUsize,
+    listens: DashMap<usize, Vec<i64>>,
+    artists: DashMap<usize, Artist>,
+    next_artist_id: AtomicUsize,
+    albums: DashMap<usize, Album>,
+    next_album_id: AtomicUsize,
+}
+
+impl MemoryStorage {
+    pub fn new() -> Self {
+        Self {
+            songs: DashMap::new(),
+            next_id: AtomicUsize::new(1),
+            listens: DashMap::new(),
+            artists: DashMap::new(),
+            next_artist_id: AtomicUsize::new(1),
+            albums: DashMap::new(),
+            next_album_id: AtomicUsize::new(1),
+        }
+    }
+
+    fn artist_name(&self, artist_id: usize) -> Option<String> {
+        self.artists.get(&artist_id).map(|a| a.name.clone())
+    }
+}
+
+#[async_trait]
+impl Storage for MemoryStorage {
+    async fn list_songs(&self) -> Vec<Song> {
+        self.songs.iter().map(|entry| entry.value().clone()).collect()
+    }
+
+    async fn get_song(&self, id: usize) -> Option<Song> {
+        self.songs.get(&id).map(|entry| entry.clone())
+    }
+
+    async fn insert_song(&self, new_song: NewSong) -> Result<Song, StorageError> {
+        let artist = self.find_or_create_artist(&new_song.artist).await?;
+        let album_id = match &new_song.album {
+            Some(title) => Some(self.find_or_create_album(title, artist.id, None).await?.id),
+            None => None,
+        };
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let song = Song {
+            id,
+            title: new_song.title,
+            artist_id: artist.id,
+            album_id,
+            genre: new_song.genre,
+            play_count: 0,
+            starred: false,
+            rating: None,
+            file_path: None,
+            track_number: None,
+            duration: None,
+        };
+        self.songs.insert(id, song.clone());
+        Ok(song)
+    }
+
+    async fn increment_play_count(&self, id: usize) -> Result<Option<Song>, StorageError> {
+        let song = self.songs.get_mut(&id).map(|mut entry| {
+            entry.play_count += 1;
+            entry.clone()
+        });
+        if song.is_some() {
+            self.listens.entry(id).or_default().push(time::now());
+        }
+        Ok(song)
+    }
+
+    async fn search(&self, query: &HashMap<String, String>) -> Vec<Song> {
+        self.songs
+            .iter()
+            .filter(|entry| {
+                let song = entry.value();
+                query.iter().all(|(key, value)| match key.as_str() {
+                    "title" => song.title.contains(value),
+                    "artist" => self
+                        .artist_name(song.artist_id)
+                        .is_some_and(|name| name.contains(value)),
+                    "genre" => song.genre.contains(value),
+                    _ => false,
+                })
+            })
+            .map(|entry| entry.value().clone())
+            .collect()
+    }
+
+    async fn set_starred(&self, id: usize, starred: bool) -> Result<Option<Song>, StorageError> {
+        Ok(self.songs.get_mut(&id).map(|mut entry| {
+            entry.starred = starred;
+            entry.clone()
+        }))
+    }
+
+    async fn set_rating(&self, id: usize, rating: u8) -> Result<Option<Song>, StorageError> {
+        Ok(self.songs.get_mut(&id).map(|mut entry| {
+            entry.rating = Some(rating);
+            entry.clone()
+        }))
+    }
+
+    async fn scrobble(&self, id: usize) -> Result<Option<Song>, StorageError> {
+        self.increment_play_count(id).await
+    }
+
+    async fn list_starred(&self) -> Vec<Song> {
+        self.songs
+            .iter()
+            .filter(|entry| entry.value().starred)
+            .map(|entry| entry.value().clone())
+            .collect()
+    }
+
+    async fn find_by_path(&self, path: &str) -> Option<Song> {
+        self.songs
+            .iter()
+            .find(|entry| entry.value().file_path.as_deref() == Some(path))
+            .map(|entry| entry.value().clone())
+    }
+
+    async fn insert_scanned_song(&self, scanned: ScannedSong) -> Result<Song, StorageError> {
+        let artist = self.find_or_create_artist(&scanned.artist).await?;
+        let album_id = match scanned.album {
+            Some(title) => Some(
+                self.find_or_create_album(&title, artist.id, scanned.year)
+                    .await?
+                    .id,
+            ),
+            None => None,
+        };
+
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let song = Song {
+            id,
+            title: scanned.title,
+            artist_id: artist.id,
+            album_id,
+            genre: scanned.genre,
+            play_count: 0,
+            starred: false,
+            rating: None,
+            file_path: Some(scanned.file_path),
+            track_number: scanned.track_number,
+            duration: scanned.duration,
+        };
+        self.songs.insert(id, song.clone());
+        Ok(song)
+    }
+
+    async fn listen_events(&self, since: Option<i64>) -> Vec<ListenEvent> {
+        self.listens
+            .iter()
+            .flat_map(|entry| {
+                let song_id = *entry.key();
+                entry
+                    .value()
+                    .iter()
+                    .filter(|&&timestamp| since.is_none_or(|since| timestamp >= since))
+                    .map(|_| ListenEvent { song_id })
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    async fn find_or_create_artist(&self, name: &str) -> Result<Artist, StorageError> {
+        if let Some(existing) = self.artists.iter().find(|entry| entry.value().name == name) {
+            return Ok(existing.value().clone());
+        }
+        let id = self.next_artist_id.fetch_add(1, Ordering::SeqCst);
+        let artist = Artist {
+            id,
+            name: name.to_string(),
+        };
+        self.artists.insert(id, artist.clone());
+        Ok(artist)
+    }
+
+    async fn get_artist(&self, id: usize) -> Option<Artist> {
+        self.artists.get(&id).map(|entry| entry.clone())
+    }
+
+    async fn list_artists(&self) -> Vec<Artist> {
+        self.artists.iter().map(|entry| entry.value().clone()).collect()
+    }
+
+    async fn find_or_create_album(
+        &self,
+        title: &str,
+        artist_id: usize,
+        year: Option<i32>,
+    ) -> Result<Album, StorageError> {
+        if let Some(existing) = self
+            .albums
+            .iter()
+            .find(|entry| entry.value().title == title && entry.value().artist_id == artist_id)
+        {
+            return Ok(existing.value().clone());
+        }
+        let id = self.next_album_id.fetch_add(1, Ordering::SeqCst);
+        let album = Album {
+            id,
+            title: title.to_string(),
+            artist_id,
+            year,
+        };
+        self.albums.insert(id, album.clone());
+        Ok(album)
+    }
+
+    async fn list_albums_by_artist(&self, artist_id: usize) -> Vec<Album> {
+        self.albums
+            .iter()
+            .filter(|entry| entry.value().artist_id == artist_id)
+            .map(|entry| entry.value().clone())
+            .collect()
+    }
+
+    async fn list_songs_by_album(&self, album_id: usize) -> Vec<Song> {
+        self.songs
+            .iter()
+            .filter(|entry| entry.value().album_id == Some(album_id))
+            .map(|entry| entry.value().clone())
+            .collect()
+    }
+}
@@ -0,0 +1,104 @@
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::models::{Album, Artist, ListenEvent, NewSong, ScannedSong, Song};
+
+/// A backend failure that the caller can't recover from on its own (a
+/// disk write failing, a database connection dropping mid-query). Routes
+/// surface this as `ApiResponse::Fatal` rather than treating it the same
+/// as a missing resource.
+#[derive(Debug)]
+pub struct StorageError(pub String);
+
+impl StorageError {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self(message.into())
+    }
+}
+
+impl fmt::Display for StorageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for StorageError {}
+
+pub mod time {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    /// Current Unix timestamp in seconds, used to stamp listen events.
+    pub fn now() -> i64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(0)
+    }
+}
+
+mod json_file;
+mod memory;
+mod sqlite;
+
+pub use json_file::JsonFileStorage;
+pub use memory::MemoryStorage;
+pub use sqlite::SqliteStorage;
+
+/// Persistence abstraction for the song library. Every route talks to the
+/// library through this trait so the backend (in-memory, a JSON file, or a
+/// real database) can be swapped without touching handler code.
+#[async_trait]
+pub trait Storage: Send + Sync {
+    async fn list_songs(&self) -> Vec<Song>;
+    async fn get_song(&self, id: usize) -> Option<Song>;
+    /// Inserts a new song. Fails only on a genuine backend error (disk
+    /// write, DB connection); a `Result` is threaded through this and the
+    /// trait's other write paths so handlers can tell that apart from a
+    /// plain "not found" and surface `ApiResponse::Fatal` instead.
+    async fn insert_song(&self, new_song: NewSong) -> Result<Song, StorageError>;
+    /// Bumps `play_count` and logs a timestamped listen event, so a play
+    /// counts toward windowed chart periods the same way a scrobble does.
+    async fn increment_play_count(&self, id: usize) -> Result<Option<Song>, StorageError>;
+    async fn search(&self, query: &HashMap<String, String>) -> Vec<Song>;
+
+    /// Sets or clears the "starred" flag on a song.
+    async fn set_starred(&self, id: usize, starred: bool) -> Result<Option<Song>, StorageError>;
+    /// Sets a song's rating. Callers are expected to validate `rating` is
+    /// in `1..=5` before calling this.
+    async fn set_rating(&self, id: usize, rating: u8) -> Result<Option<Song>, StorageError>;
+    /// Records a listen: bumps `play_count` and logs a timestamped event.
+    /// Equivalent to [`Storage::increment_play_count`]; kept as its own
+    /// route for clients that want to distinguish "played" from "scrobbled".
+    async fn scrobble(&self, id: usize) -> Result<Option<Song>, StorageError>;
+    /// Returns only starred songs.
+    async fn list_starred(&self) -> Vec<Song>;
+
+    /// Looks up a song by its source file path, used by the library scan
+    /// to skip files it has already indexed.
+    async fn find_by_path(&self, path: &str) -> Option<Song>;
+    /// Inserts a song discovered by a library scan.
+    async fn insert_scanned_song(&self, song: ScannedSong) -> Result<Song, StorageError>;
+
+    /// Returns listen events at or after `since` (a Unix timestamp), or
+    /// every recorded event when `since` is `None`. Backs the `charts`
+    /// route's `period` filtering.
+    async fn listen_events(&self, since: Option<i64>) -> Vec<ListenEvent>;
+
+    /// Looks up an artist by name, creating it if this is the first time
+    /// it's been seen.
+    async fn find_or_create_artist(&self, name: &str) -> Result<Artist, StorageError>;
+    async fn get_artist(&self, id: usize) -> Option<Artist>;
+    async fn list_artists(&self) -> Vec<Artist>;
+    /// Looks up an album by title under a given artist, creating it (and
+    /// recording `year`, if known) if this is the first time it's been
+    /// seen.
+    async fn find_or_create_album(
+        &self,
+        title: &str,
+        artist_id: usize,
+        year: Option<i32>,
+    ) -> Result<Album, StorageError>;
+    async fn list_albums_by_artist(&self, artist_id: usize) -> Vec<Album>;
+    async fn list_songs_by_album(&self, album_id: usize) -> Vec<Song>;
+}
@@ -0,0 +1,470 @@
+use async_trait::async_trait;
+use sqlx::sqlite::SqliteConnectOptions;
+use sqlx::SqlitePool;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use super::{time, Storage, StorageError};
+use crate::models::{Album, Artist, ListenEvent, NewSong, ScannedSong, Song};
+
+const SONG_COLUMNS: &str =
+    "id, title, artist_id, album_id, genre, play_count, starred, rating, file_path, track_number, duration";
+
+/// A `songs` row as it comes back from SQLite, before narrowing the
+/// `i64` columns down to the `usize`/`u8` fields `Song` uses everywhere
+/// else.
+#[derive(sqlx::FromRow)]
+struct SongRow {
+    id: i64,
+    title: String,
+    artist_id: i64,
+    album_id: Option<i64>,
+    genre: String,
+    play_count: i64,
+    starred: i64,
+    rating: Option<i64>,
+    file_path: Option<String>,
+    track_number: Option<i64>,
+    duration: Option<f64>,
+}
+
+impl From<SongRow> for Song {
+    fn from(row: SongRow) -> Self {
+        Song {
+            id: row.id as usize,
+            title: row.title,
+            artist_id: row.artist_id as usize,
+            album_id: row.album_id.map(|id| id as usize),
+            genre: row.genre,
+            play_count: row.play_count as usize,
+            starred: row.starred != 0,
+            rating: row.rating.map(|r| r as u8),
+            file_path: row.file_path,
+            track_number: row.track_number.map(|n| n as u32),
+            duration: row.duration,
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct ArtistRow {
+    id: i64,
+    name: String,
+}
+
+impl From<ArtistRow> for Artist {
+    fn from(row: ArtistRow) -> Self {
+        Artist {
+            id: row.id as usize,
+            name: row.name,
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct AlbumRow {
+    id: i64,
+    title: String,
+    artist_id: i64,
+    year: Option<i64>,
+}
+
+impl From<AlbumRow> for Album {
+    fn from(row: AlbumRow) -> Self {
+        Album {
+            id: row.id as usize,
+            title: row.title,
+            artist_id: row.artist_id as usize,
+            year: row.year.map(|y| y as i32),
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct ListenRow {
+    song_id: i64,
+}
+
+/// SQLite-backed storage via `sqlx`. Schema is managed by the migrations
+/// under `migrations/`.
+pub struct SqliteStorage {
+    pool: SqlitePool,
+}
+
+impl SqliteStorage {
+    pub async fn connect(database_url: &str) -> Result<Self, sqlx::Error> {
+        // `SqlitePool::connect` errors with "unable to open database file"
+        // on a fresh checkout where the file doesn't exist yet; opt into
+        // creating it so this backend works without the operator knowing
+        // to append `?mode=rwc` themselves.
+        let options = SqliteConnectOptions::from_str(database_url)?.create_if_missing(true);
+        let pool = SqlitePool::connect_with(options).await?;
+        sqlx::migrate!("./migrations").run(&pool).await?;
+        Ok(Self { pool })
+    }
+
+    async fn song_by_id(&self, id: usize) -> Option<Song> {
+        sqlx::query_as::<_, SongRow>(&format!("SELECT {} FROM songs WHERE id = ?", SONG_COLUMNS))
+            .bind(id as i64)
+            .fetch_optional(&self.pool)
+            .await
+            .unwrap_or_else(|e| {
+                eprintln!("Error fetching song {}: {}", id, e);
+                None
+            })
+            .map(Song::from)
+    }
+}
+
+#[async_trait]
+impl Storage for SqliteStorage {
+    async fn list_songs(&self) -> Vec<Song> {
+        sqlx::query_as::<_, SongRow>(&format!("SELECT {} FROM songs ORDER BY id", SONG_COLUMNS))
+            .fetch_all(&self.pool)
+            .await
+            .unwrap_or_else(|e| {
+                eprintln!("Error listing songs: {}", e);
+                Vec::new()
+            })
+            .into_iter()
+            .map(Song::from)
+            .collect()
+    }
+
+    async fn get_song(&self, id: usize) -> Option<Song> {
+        self.song_by_id(id).await
+    }
+
+    async fn insert_song(&self, new_song: NewSong) -> Result<Song, StorageError> {
+        let artist = self.find_or_create_artist(&new_song.artist).await?;
+        let album_id = match &new_song.album {
+            Some(title) => Some(self.find_or_create_album(title, artist.id, None).await?.id),
+            None => None,
+        };
+
+        let result = sqlx::query(
+            "INSERT INTO songs (title, artist_id, album_id, genre, play_count) VALUES (?, ?, ?, ?, 0)",
+        )
+        .bind(&new_song.title)
+        .bind(artist.id as i64)
+        .bind(album_id.map(|id| id as i64))
+        .bind(&new_song.genre)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| StorageError::new(format!("failed to insert song: {}", e)))?;
+
+        Ok(Song {
+            id: result.last_insert_rowid() as usize,
+            title: new_song.title,
+            artist_id: artist.id,
+            album_id,
+            genre: new_song.genre,
+            play_count: 0,
+            starred: false,
+            rating: None,
+            file_path: None,
+            track_number: None,
+            duration: None,
+        })
+    }
+
+    async fn increment_play_count(&self, id: usize) -> Result<Option<Song>, StorageError> {
+        let result = sqlx::query("UPDATE songs SET play_count = play_count + 1 WHERE id = ?")
+            .bind(id as i64)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| StorageError::new(format!("failed to increment play count: {}", e)))?;
+
+        if result.rows_affected() == 0 {
+            return Ok(None);
+        }
+
+        sqlx::query("INSERT INTO listens (song_id, listened_at) VALUES (?, ?)")
+            .bind(id as i64)
+            .bind(time::now())
+            .execute(&self.pool)
+            .await
+            .map_err(|e| {
+                StorageError::new(format!("failed to record listen for song {}: {}", id, e))
+            })?;
+
+        Ok(self.song_by_id(id).await)
+    }
+
+    async fn search(&self, query: &HashMap<String, String>) -> Vec<Song> {
+        // SQLite has no query builder here, so filter the patterns we
+        // understand in memory over the full table rather than growing a
+        // dynamic WHERE clause for a handful of optional fields.
+        let artist_names: HashMap<usize, String> = self
+            .list_artists()
+            .await
+            .into_iter()
+            .map(|artist| (artist.id, artist.name))
+            .collect();
+
+        self.list_songs()
+            .await
+            .into_iter()
+            .filter(|song| {
+                query.iter().all(|(key, value)| match key.as_str() {
+                    "title" => song.title.contains(value.as_str()),
+                    "artist" => artist_names
+                        .get(&song.artist_id)
+                        .is_some_and(|name| name.contains(value.as_str())),
+                    "genre" => song.genre.contains(value.as_str()),
+                    _ => false,
+                })
+            })
+            .collect()
+    }
+
+    async fn set_starred(&self, id: usize, starred: bool) -> Result<Option<Song>, StorageError> {
+        let result = sqlx::query("UPDATE songs SET starred = ? WHERE id = ?")
+            .bind(starred)
+            .bind(id as i64)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| StorageError::new(format!("failed to update starred: {}", e)))?;
+
+        if result.rows_affected() == 0 {
+            return Ok(None);
+        }
+
+        Ok(self.song_by_id(id).await)
+    }
+
+    async fn set_rating(&self, id: usize, rating: u8) -> Result<Option<Song>, StorageError> {
+        let result = sqlx::query("UPDATE songs SET rating = ? WHERE id = ?")
+            .bind(rating)
+            .bind(id as i64)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| StorageError::new(format!("failed to update rating: {}", e)))?;
+
+        if result.rows_affected() == 0 {
+            return Ok(None);
+        }
+
+        Ok(self.song_by_id(id).await)
+    }
+
+    async fn scrobble(&self, id: usize) -> Result<Option<Song>, StorageError> {
+        self.increment_play_count(id).await
+    }
+
+    async fn list_starred(&self) -> Vec<Song> {
+        sqlx::query_as::<_, SongRow>(&format!(
+            "SELECT {} FROM songs WHERE starred = 1 ORDER BY id",
+            SONG_COLUMNS
+        ))
+        .fetch_all(&self.pool)
+        .await
+        .unwrap_or_else(|e| {
+            eprintln!("Error listing starred songs: {}", e);
+            Vec::new()
+        })
+        .into_iter()
+        .map(Song::from)
+        .collect()
+    }
+
+    async fn find_by_path(&self, path: &str) -> Option<Song> {
+        sqlx::query_as::<_, SongRow>(&format!(
+            "SELECT {} FROM songs WHERE file_path = ?",
+            SONG_COLUMNS
+        ))
+        .bind(path)
+        .fetch_optional(&self.pool)
+        .await
+        .unwrap_or_else(|e| {
+            eprintln!("Error looking up song by path {}: {}", path, e);
+            None
+        })
+        .map(Song::from)
+    }
+
+    async fn insert_scanned_song(&self, scanned: ScannedSong) -> Result<Song, StorageError> {
+        let artist = self.find_or_create_artist(&scanned.artist).await?;
+        let album_id = match &scanned.album {
+            Some(title) => Some(
+                self.find_or_create_album(title, artist.id, scanned.year)
+                    .await?
+                    .id,
+            ),
+            None => None,
+        };
+
+        let track_number = scanned.track_number.map(|n| n as i64);
+        let result = sqlx::query(
+            "INSERT INTO songs (title, artist_id, album_id, genre, play_count, file_path, track_number, duration) \
+             VALUES (?, ?, ?, ?, 0, ?, ?, ?)",
+        )
+        .bind(&scanned.title)
+        .bind(artist.id as i64)
+        .bind(album_id.map(|id| id as i64))
+        .bind(&scanned.genre)
+        .bind(&scanned.file_path)
+        .bind(track_number)
+        .bind(scanned.duration)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| StorageError::new(format!("failed to insert scanned song: {}", e)))?;
+
+        Ok(Song {
+            id: result.last_insert_rowid() as usize,
+            title: scanned.title,
+            artist_id: artist.id,
+            album_id,
+            genre: scanned.genre,
+            play_count: 0,
+            starred: false,
+            rating: None,
+            file_path: Some(scanned.file_path),
+            track_number: scanned.track_number,
+            duration: scanned.duration,
+        })
+    }
+
+    async fn listen_events(&self, since: Option<i64>) -> Vec<ListenEvent> {
+        let rows = match since {
+            Some(since) => {
+                sqlx::query_as::<_, ListenRow>(
+                    "SELECT song_id FROM listens WHERE listened_at >= ?",
+                )
+                .bind(since)
+                .fetch_all(&self.pool)
+                .await
+            }
+            None => {
+                sqlx::query_as::<_, ListenRow>("SELECT song_id FROM listens")
+                    .fetch_all(&self.pool)
+                    .await
+            }
+        };
+
+        rows.unwrap_or_else(|e| {
+            eprintln!("Error listing listen events: {}", e);
+            Vec::new()
+        })
+        .into_iter()
+        .map(|row| ListenEvent {
+            song_id: row.song_id as usize,
+        })
+        .collect()
+    }
+
+    async fn find_or_create_artist(&self, name: &str) -> Result<Artist, StorageError> {
+        if let Some(row) =
+            sqlx::query_as::<_, ArtistRow>("SELECT id, name FROM artists WHERE name = ?")
+                .bind(name)
+                .fetch_optional(&self.pool)
+                .await
+                .unwrap_or(None)
+        {
+            return Ok(Artist::from(row));
+        }
+
+        let result = sqlx::query("INSERT INTO artists (name) VALUES (?)")
+            .bind(name)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| StorageError::new(format!("failed to insert artist: {}", e)))?;
+
+        Ok(Artist {
+            id: result.last_insert_rowid() as usize,
+            name: name.to_string(),
+        })
+    }
+
+    async fn get_artist(&self, id: usize) -> Option<Artist> {
+        sqlx::query_as::<_, ArtistRow>("SELECT id, name FROM artists WHERE id = ?")
+            .bind(id as i64)
+            .fetch_optional(&self.pool)
+            .await
+            .unwrap_or_else(|e| {
+                eprintln!("Error fetching artist {}: {}", id, e);
+                None
+            })
+            .map(Artist::from)
+    }
+
+    async fn list_artists(&self) -> Vec<Artist> {
+        sqlx::query_as::<_, ArtistRow>("SELECT id, name FROM artists ORDER BY name")
+            .fetch_all(&self.pool)
+            .await
+            .unwrap_or_else(|e| {
+                eprintln!("Error listing artists: {}", e);
+                Vec::new()
+            })
+            .into_iter()
+            .map(Artist::from)
+            .collect()
+    }
+
+    async fn find_or_create_album(
+        &self,
+        title: &str,
+        artist_id: usize,
+        year: Option<i32>,
+    ) -> Result<Album, StorageError> {
+        if let Some(row) = sqlx::query_as::<_, AlbumRow>(
+            "SELECT id, title, artist_id, year FROM albums WHERE title = ? AND artist_id = ?",
+        )
+        .bind(title)
+        .bind(artist_id as i64)
+        .fetch_optional(&self.pool)
+        .await
+        .unwrap_or(None)
+        {
+            return Ok(Album::from(row));
+        }
+
+        let result = sqlx::query("INSERT INTO albums (title, artist_id, year) VALUES (?, ?, ?)")
+            .bind(title)
+            .bind(artist_id as i64)
+            .bind(year)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| StorageError::new(format!("failed to insert album: {}", e)))?;
+
+        Ok(Album {
+            id: result.last_insert_rowid() as usize,
+            title: title.to_string(),
+            artist_id,
+            year,
+        })
+    }
+
+    async fn list_albums_by_artist(&self, artist_id: usize) -> Vec<Album> {
+        sqlx::query_as::<_, AlbumRow>(
+            "SELECT id, title, artist_id, year FROM albums WHERE artist_id = ? ORDER BY year",
+        )
+        .bind(artist_id as i64)
+        .fetch_all(&self.pool)
+        .await
+        .unwrap_or_else(|e| {
+            eprintln!("Error listing albums for artist {}: {}", artist_id, e);
+            Vec::new()
+        })
+        .into_iter()
+        .map(Album::from)
+        .collect()
+    }
+
+    async fn list_songs_by_album(&self, album_id: usize) -> Vec<Song> {
+        sqlx::query_as::<_, SongRow>(&format!(
+            "SELECT {} FROM songs WHERE album_id = ? ORDER BY track_number",
+            SONG_COLUMNS
+        ))
+        .bind(album_id as i64)
+        .fetch_all(&self.pool)
+        .await
+        .unwrap_or_else(|e| {
+            eprintln!("Error listing songs for album {}: {}", album_id, e);
+            Vec::new()
+        })
+        .into_iter()
+        .map(Song::from)
+        .collect()
+    }
+}
@@ -0,0 +1,94 @@
+use std::path::Path;
+use tokio::fs::File;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+use tokio_util::io::ReaderStream;
+use warp::http::{header, Response, StatusCode};
+use warp::hyper::Body;
+use warp::Rejection;
+
+/// Parses a single `Range: bytes=start-end` header value. Multi-range
+/// requests aren't supported; anything we can't parse is treated as "no
+/// range", which falls back to a full 200 response.
+fn parse_range(header: &str, file_size: u64) -> Option<(u64, u64)> {
+    let spec = header.strip_prefix("bytes=")?;
+    let (start, end) = spec.split_once('-')?;
+
+    // `bytes=-N` is a suffix range: the last N bytes of the file, not an
+    // omitted start of 0.
+    if start.is_empty() {
+        let suffix_len: u64 = end.parse().ok()?;
+        if suffix_len == 0 || file_size == 0 {
+            return None;
+        }
+        let start = file_size.saturating_sub(suffix_len);
+        return Some((start, file_size - 1));
+    }
+
+    let start: u64 = start.parse().ok()?;
+    let end: u64 = if end.is_empty() {
+        file_size.saturating_sub(1)
+    } else {
+        end.parse().ok()?
+    };
+
+    if start > end || end >= file_size {
+        return None;
+    }
+    Some((start, end))
+}
+
+fn content_type_for(path: &str) -> &'static str {
+    match Path::new(path).extension().and_then(|ext| ext.to_str()) {
+        Some("mp3") => "audio/mpeg",
+        Some("flac") => "audio/flac",
+        Some("wav") => "audio/wav",
+        Some("ogg") => "audio/ogg",
+        Some("m4a") => "audio/mp4",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Streams `path` as a warp reply, honoring a `Range` header by seeking to
+/// the requested offset and bounding the read rather than loading the
+/// whole file into memory. Falls back to a full 200 response when no
+/// (valid) range is present.
+pub async fn stream_file(
+    path: &str,
+    range: Option<String>,
+) -> Result<Response<Body>, Rejection> {
+    let metadata = tokio::fs::metadata(path)
+        .await
+        .map_err(|_| warp::reject::not_found())?;
+    let file_size = metadata.len();
+
+    let mut file = File::open(path).await.map_err(|_| warp::reject::not_found())?;
+
+    let (start, end, status) = match range.as_deref().and_then(|h| parse_range(h, file_size)) {
+        Some((start, end)) => (start, end, StatusCode::PARTIAL_CONTENT),
+        None => (0, file_size.saturating_sub(1), StatusCode::OK),
+    };
+
+    file.seek(std::io::SeekFrom::Start(start))
+        .await
+        .map_err(|_| warp::reject::not_found())?;
+
+    let len = end - start + 1;
+    let stream = ReaderStream::new(file.take(len));
+
+    let mut builder = Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, content_type_for(path))
+        .header(header::ACCEPT_RANGES, "bytes")
+        .header(header::CONTENT_LENGTH, len.to_string());
+
+    if status == StatusCode::PARTIAL_CONTENT {
+        builder = builder.header(
+            header::CONTENT_RANGE,
+            format!("bytes {}-{}/{}", start, end, file_size),
+        );
+    }
+
+    builder
+        .body(Body::wrap_stream(stream))
+        .map_err(|_| warp::reject::not_found())
+}